@@ -0,0 +1,15 @@
+mod route_error;
+mod route_error_output;
+mod route_error_status;
+mod route_internal_error;
+mod route_internal_error_output;
+mod route_problem_error;
+mod route_problem_output;
+
+pub use self::route_error::RouteError;
+pub use self::route_error_output::RouteErrorOutput;
+pub use self::route_error_status::RouteErrorStatus;
+pub use self::route_internal_error::RouteInternalError;
+pub use self::route_internal_error_output::RouteInternalErrorOutput;
+pub use self::route_problem_error::RouteProblemError;
+pub use self::route_problem_output::RouteProblemOutput;
@@ -1,17 +1,30 @@
 use anyhow::Error as AnyhowError;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::header::RETRY_AFTER;
+use axum::http::HeaderValue;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::response::Response;
 use axum::Json;
 use serde::Deserialize;
 use serde::Serialize;
+use std::error::Error as StdError;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tracing::Level;
 
 use super::RouteErrorOutput;
+use crate::RouteErrorStatus;
 use crate::RouteInternalErrorOutput;
+use crate::RouteProblemOutput;
 
 /// This Rust module provides a standard error type for routes.
 /// It encapsulates information about errors that occur while handling requests.
@@ -23,10 +36,14 @@ use crate::RouteInternalErrorOutput;
 /// The output will be in the form:
 /// ```json
 ///     {
-///         "error": "My public error message"
+///         "error": "My public error message",
+///         "request_id": "abc123"
 ///     }
 /// ```
 ///
+/// `request_id` is always present, even when `EXPOSE_INTERNAL_ERROR` is
+/// `false`, so a user-reported error can be matched back to its log line.
+///
 /// Most of the time you will want to simply return one of:
 ///
 ///  - `RouteError::new_unauthorised()`
@@ -36,14 +53,34 @@ use crate::RouteInternalErrorOutput;
 ///
 /// Depending on which is the most appropriate.
 ///
-pub struct RouteError<S = (), const EXPOSE_INTERNAL_ERROR: bool = false>
-where
+/// Use [`crate::RouteProblemError`] instead of `RouteError` if you want
+/// responses serialized as RFC 7807 `application/problem+json` documents.
+///
+pub struct RouteError<
+    S = (),
+    const EXPOSE_INTERNAL_ERROR: bool = false,
+    const PROBLEM_JSON: bool = false,
+> where
     S: Serialize + for<'a> Deserialize<'a> + Debug,
 {
     status_code: StatusCode,
     error: Option<AnyhowError>,
     extra_data: Option<Box<S>>,
     public_error_message: Option<String>,
+    extras: Option<Box<ErrorExtras>>,
+}
+
+/// The less commonly set fields, boxed together and allocated lazily so
+/// that `RouteError` (the `Err` type of most handler `Result`s in
+/// consuming code) stays small on the happy path.
+#[derive(Default)]
+struct ErrorExtras {
+    retry_after: Option<Instant>,
+    problem_type: Option<String>,
+    problem_instance: Option<String>,
+    problem_detail: Option<String>,
+    log_level: Option<Level>,
+    request_id: Option<String>,
 }
 
 impl RouteError<()> {
@@ -79,7 +116,76 @@ impl RouteError<()> {
     }
 }
 
-impl<S, const EXPOSE_INTERNAL_ERROR: bool> RouteError<S, EXPOSE_INTERNAL_ERROR>
+impl<const EXPOSE_INTERNAL_ERROR: bool, const PROBLEM_JSON: bool>
+    RouteError<(), EXPOSE_INTERNAL_ERROR, PROBLEM_JSON>
+{
+    /// Build a `RouteError` from an error that implements
+    /// [`RouteErrorStatus`], keeping its intended status code and public
+    /// message instead of collapsing to `500` like the blanket
+    /// `From<AnyhowError>` impl does.
+    ///
+    /// Works for any combination of `EXPOSE_INTERNAL_ERROR`/`PROBLEM_JSON`,
+    /// so it can be used to build a [`crate::RouteInternalError`] or
+    /// [`crate::RouteProblemError`] just as well as a plain `RouteError` —
+    /// the underlying error is always captured, it's only exposed in the
+    /// response when `EXPOSE_INTERNAL_ERROR` is `true`.
+    ///
+    /// # Example Code
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_route_error::RouteError;
+    /// use axum_route_error::RouteErrorStatus;
+    /// use std::fmt;
+    ///
+    /// #[derive(Debug)]
+    /// enum UserError {
+    ///     NotFound,
+    ///     PasswordTooShort,
+    /// }
+    ///
+    /// impl fmt::Display for UserError {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         match self {
+    ///             UserError::NotFound => write!(f, "user not found"),
+    ///             UserError::PasswordTooShort => write!(f, "password too short"),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for UserError {}
+    ///
+    /// impl RouteErrorStatus for UserError {
+    ///     fn route_status(&self) -> StatusCode {
+    ///         match self {
+    ///             UserError::NotFound => StatusCode::NOT_FOUND,
+    ///             UserError::PasswordTooShort => StatusCode::BAD_REQUEST,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let err: RouteError = RouteError::from_status_err(UserError::NotFound);
+    /// assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+    /// ```
+    pub fn from_status_err<E>(err: E) -> Self
+    where
+        E: RouteErrorStatus + StdError + Send + Sync + 'static,
+    {
+        let status_code = err.route_status();
+        let public_error_message = err.public_message();
+        let anyhow_error = AnyhowError::new(err);
+
+        Self {
+            status_code,
+            error: Some(anyhow_error),
+            public_error_message,
+            ..Self::default()
+        }
+    }
+}
+
+impl<S, const EXPOSE_INTERNAL_ERROR: bool, const PROBLEM_JSON: bool>
+    RouteError<S, EXPOSE_INTERNAL_ERROR, PROBLEM_JSON>
 where
     S: Serialize + for<'a> Deserialize<'a> + Debug,
 {
@@ -101,6 +207,79 @@ where
         }
     }
 
+    /// Set how long the client should wait before retrying the request.
+    ///
+    /// This is emitted as a `Retry-After` header (in delta-seconds form),
+    /// and as `retry_after_seconds` in the JSON body of the default output
+    /// shape. The `application/problem+json` body has no such member;
+    /// there only the header is set.
+    pub fn set_retry_after(self, duration: Duration) -> Self {
+        self.set_retry_after_at(Instant::now() + duration)
+    }
+
+    /// Set the instant at which the client should retry the request.
+    ///
+    /// The `Retry-After` header and (in the default output shape) the
+    /// `retry_after_seconds` field are computed from this instant when
+    /// the response is built. If the
+    /// instant has already passed, `0` is emitted.
+    pub fn set_retry_after_at(mut self, instant: Instant) -> Self {
+        self.extras_mut().retry_after = Some(instant);
+        self
+    }
+
+    /// Set the `type` URI used when this error is serialized as an
+    /// RFC 7807 `application/problem+json` document.
+    ///
+    /// Defaults to `about:blank` when not set.
+    pub fn set_problem_type(mut self, problem_type: &str) -> Self {
+        self.extras_mut().problem_type = Some(problem_type.to_string());
+        self
+    }
+
+    /// Set the `instance` URI used when this error is serialized as an
+    /// RFC 7807 `application/problem+json` document.
+    pub fn set_problem_instance(mut self, problem_instance: &str) -> Self {
+        self.extras_mut().problem_instance = Some(problem_instance.to_string());
+        self
+    }
+
+    /// Set the `detail` member used when this error is serialized as an
+    /// RFC 7807 `application/problem+json` document.
+    ///
+    /// This is a human-readable explanation specific to this occurrence
+    /// of the problem, as opposed to `title`'s generic summary.
+    pub fn set_problem_detail(mut self, problem_detail: &str) -> Self {
+        self.extras_mut().problem_detail = Some(problem_detail.to_string());
+        self
+    }
+
+    /// Override the `tracing` level this error is logged at.
+    ///
+    /// If not set, the level is picked from the response's status code:
+    /// `5xx` logs at `Level::ERROR`, everything else at `Level::WARN`.
+    pub fn set_log_level(mut self, log_level: Level) -> Self {
+        self.extras_mut().log_level = Some(log_level);
+        self
+    }
+
+    /// Set a correlation/request id for this error.
+    ///
+    /// It is included in the log line and as `request_id` in the JSON
+    /// body, regardless of `EXPOSE_INTERNAL_ERROR`, so a user-reported
+    /// error can be matched back to its log entry. If left unset, a
+    /// short id is generated automatically when the response is built.
+    pub fn set_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.extras_mut().request_id = Some(request_id.into());
+        self
+    }
+
+    /// Returns the extras box, allocating an empty one first if unset.
+    fn extras_mut(&mut self) -> &mut ErrorExtras {
+        self.extras
+            .get_or_insert_with(|| Box::new(ErrorExtras::default()))
+    }
+
     ///
     /// Sets additional error data to be added to the output.
     /// Data here must be serialisable into Json.
@@ -142,6 +321,7 @@ where
             status_code: self.status_code,
             error: self.error,
             public_error_message: self.public_error_message,
+            extras: self.extras,
         }
     }
 
@@ -171,7 +351,8 @@ where
     }
 }
 
-impl<S, const EXPOSE_INTERNAL_ERROR: bool> Default for RouteError<S, EXPOSE_INTERNAL_ERROR>
+impl<S, const EXPOSE_INTERNAL_ERROR: bool, const PROBLEM_JSON: bool> Default
+    for RouteError<S, EXPOSE_INTERNAL_ERROR, PROBLEM_JSON>
 where
     S: Serialize + for<'a> Deserialize<'a> + Debug,
 {
@@ -181,11 +362,13 @@ where
             error: None,
             extra_data: None,
             public_error_message: None,
+            extras: None,
         }
     }
 }
 
-impl<S, const EXPOSE_INTERNAL_ERROR: bool> IntoResponse for RouteError<S, EXPOSE_INTERNAL_ERROR>
+impl<S, const EXPOSE_INTERNAL_ERROR: bool, const PROBLEM_JSON: bool> IntoResponse
+    for RouteError<S, EXPOSE_INTERNAL_ERROR, PROBLEM_JSON>
 where
     S: Serialize + for<'a> Deserialize<'a> + Debug,
 {
@@ -196,29 +379,71 @@ where
             Some(public_error_message) => public_error_message,
             None => status_code_to_public_message(status).to_string(),
         };
+        let extras = self.extras.map(|extras| *extras).unwrap_or_default();
+        let retry_after_seconds = extras.retry_after.map(retry_after_to_seconds);
+        let request_id = extras.request_id.unwrap_or_else(generate_request_id);
+
+        if let Some(err) = self.error.as_ref() {
+            let log_level = extras
+                .log_level
+                .unwrap_or_else(|| status_code_to_log_level(status));
+            log_error(log_level, err, &request_id);
+        }
 
         let internal_error = if EXPOSE_INTERNAL_ERROR {
             self.error.map(|err| RouteInternalErrorOutput {
                 name: format!("{}", err),
                 debug: format!("{:?}", err),
+                chain: err.chain().map(|cause| format!("{}", cause)).collect(),
+                backtrace: backtrace_to_string(err.backtrace()),
             })
         } else {
             None
         };
 
-        let output = RouteErrorOutput {
-            error,
-            internal_error,
-            extra_data,
-            ..RouteErrorOutput::default()
+        let mut response = if PROBLEM_JSON {
+            let output = RouteProblemOutput {
+                problem_type: extras
+                    .problem_type
+                    .unwrap_or_else(|| "about:blank".to_string()),
+                title: error,
+                status: status.as_u16(),
+                detail: extras.problem_detail,
+                instance: extras.problem_instance,
+                internal_error,
+                request_id,
+                extra_data,
+            };
+            let mut response = (status, Json(output)).into_response();
+            response.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/problem+json"),
+            );
+            response
+        } else {
+            let output = RouteErrorOutput {
+                error,
+                internal_error,
+                extra_data,
+                retry_after_seconds,
+                request_id,
+                ..RouteErrorOutput::default()
+            };
+            (status, Json(output)).into_response()
         };
-        let body = Json(output);
 
-        (status, body).into_response()
+        if let Some(seconds) = retry_after_seconds {
+            if let Ok(value) = HeaderValue::from_str(&seconds.to_string()) {
+                response.headers_mut().insert(RETRY_AFTER, value);
+            }
+        }
+
+        response
     }
 }
 
-impl<S, const EXPOSE_INTERNAL_ERROR: bool> Debug for RouteError<S, EXPOSE_INTERNAL_ERROR>
+impl<S, const EXPOSE_INTERNAL_ERROR: bool, const PROBLEM_JSON: bool> Debug
+    for RouteError<S, EXPOSE_INTERNAL_ERROR, PROBLEM_JSON>
 where
     S: Serialize + for<'a> Deserialize<'a> + Debug,
 {
@@ -227,7 +452,8 @@ where
     }
 }
 
-impl<S, const EXPOSE_INTERNAL_ERROR: bool> Display for RouteError<S, EXPOSE_INTERNAL_ERROR>
+impl<S, const EXPOSE_INTERNAL_ERROR: bool, const PROBLEM_JSON: bool> Display
+    for RouteError<S, EXPOSE_INTERNAL_ERROR, PROBLEM_JSON>
 where
     S: Serialize + for<'a> Deserialize<'a> + Debug,
 {
@@ -238,14 +464,14 @@ where
 
 /// This essentially means if you can turn it into an Anyhow,
 /// then you can turn it into a RouteError.
-impl<S, const EXPOSE_INTERNAL_ERROR: bool, FE> From<FE> for RouteError<S, EXPOSE_INTERNAL_ERROR>
+impl<S, const EXPOSE_INTERNAL_ERROR: bool, const PROBLEM_JSON: bool, FE> From<FE>
+    for RouteError<S, EXPOSE_INTERNAL_ERROR, PROBLEM_JSON>
 where
     S: Serialize + for<'a> Deserialize<'a> + Debug,
     FE: Into<AnyhowError>,
 {
     fn from(error: FE) -> Self {
         let anyhow_error: AnyhowError = error.into();
-        ::tracing::error!("{:?}", anyhow_error);
 
         RouteError {
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
@@ -255,6 +481,52 @@ where
     }
 }
 
+fn status_code_to_log_level(status_code: StatusCode) -> Level {
+    if status_code.is_server_error() {
+        Level::ERROR
+    } else {
+        Level::WARN
+    }
+}
+
+fn log_error(level: Level, error: &AnyhowError, request_id: &str) {
+    match level {
+        Level::ERROR => ::tracing::error!(request_id, "{:?}", error),
+        Level::WARN => ::tracing::warn!(request_id, "{:?}", error),
+        Level::INFO => ::tracing::info!(request_id, "{:?}", error),
+        Level::DEBUG => ::tracing::debug!(request_id, "{:?}", error),
+        Level::TRACE => ::tracing::trace!(request_id, "{:?}", error),
+    }
+}
+
+/// Generates a short, unique-enough id for correlating a response with
+/// its log entry. Not cryptographically random, just distinct per process.
+fn generate_request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+
+    format!("{:x}-{:x}", nanos, counter)
+}
+
+fn retry_after_to_seconds(instant: Instant) -> u64 {
+    instant
+        .checked_duration_since(Instant::now())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn backtrace_to_string(backtrace: &std::backtrace::Backtrace) -> Option<String> {
+    match backtrace.status() {
+        std::backtrace::BacktraceStatus::Captured => Some(format!("{}", backtrace)),
+        _ => None,
+    }
+}
+
 fn status_code_to_public_message(status_code: StatusCode) -> &'static str {
     match status_code {
         StatusCode::CONFLICT => "The request is not allowed",
@@ -297,4 +569,91 @@ mod test_route_error {
 
         assert_eq!(body.internal_error, None);
     }
+
+    #[tokio::test]
+    async fn it_should_output_retry_after_header_and_body_field() {
+        let err = RouteError::new_from_status(StatusCode::TOO_MANY_REQUESTS)
+            .set_retry_after(Duration::from_secs(30));
+        let response = err.into_response();
+
+        let retry_after_header = response
+            .headers()
+            .get(RETRY_AFTER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse::<u64>()
+            .unwrap();
+        assert!(retry_after_header > 0 && retry_after_header <= 30);
+
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<RouteErrorOutput<()>>(&response_bytes).unwrap();
+
+        let retry_after_seconds = body.retry_after_seconds.unwrap();
+        assert!(retry_after_seconds > 0 && retry_after_seconds <= 30);
+    }
+
+    #[tokio::test]
+    async fn it_should_clamp_a_past_retry_after_to_zero() {
+        let err = RouteError::new_from_status(StatusCode::SERVICE_UNAVAILABLE)
+            .set_retry_after_at(Instant::now() - Duration::from_secs(5));
+        let response = err.into_response();
+
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "0");
+
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<RouteErrorOutput<()>>(&response_bytes).unwrap();
+
+        assert_eq!(body.retry_after_seconds, Some(0));
+    }
+
+    #[tokio::test]
+    async fn it_should_generate_a_request_id_when_none_is_set() {
+        let err = RouteError::new_not_found();
+        let response = err.into_response();
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<RouteErrorOutput<()>>(&response_bytes).unwrap();
+
+        assert!(!body.request_id.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_use_an_explicitly_set_request_id() {
+        let err = RouteError::new_not_found().set_request_id("my-request-id");
+        let response = err.into_response();
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<RouteErrorOutput<()>>(&response_bytes).unwrap();
+
+        assert_eq!(body.request_id, "my-request-id");
+    }
+
+    #[test]
+    fn it_should_map_status_codes_to_log_levels() {
+        assert_eq!(
+            status_code_to_log_level(StatusCode::INTERNAL_SERVER_ERROR),
+            Level::ERROR
+        );
+        assert_eq!(
+            status_code_to_log_level(StatusCode::BAD_GATEWAY),
+            Level::ERROR
+        );
+        assert_eq!(status_code_to_log_level(StatusCode::NOT_FOUND), Level::WARN);
+        assert_eq!(
+            status_code_to_log_level(StatusCode::BAD_REQUEST),
+            Level::WARN
+        );
+        assert_eq!(status_code_to_log_level(StatusCode::OK), Level::WARN);
+    }
+
+    #[test]
+    fn it_should_map_backtrace_status_to_an_option() {
+        use std::backtrace::Backtrace;
+
+        assert_eq!(backtrace_to_string(&Backtrace::disabled()), None);
+        assert!(backtrace_to_string(&Backtrace::force_capture()).is_some());
+    }
 }
@@ -11,6 +11,13 @@ pub struct RouteErrorOutput<S> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub internal_error: Option<RouteInternalErrorOutput>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_seconds: Option<u64>,
+
+    /// A correlation id for matching this response to its log entry.
+    /// Always present, even when `internal_error` is not exposed.
+    pub request_id: String,
+
     #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub extra_data: Option<S>,
 }
@@ -20,6 +27,8 @@ impl<S> Default for RouteErrorOutput<S> {
         Self {
             error: "An unknown error occurred".to_string(),
             internal_error: None,
+            retry_after_seconds: None,
+            request_id: String::new(),
             extra_data: None,
         }
     }
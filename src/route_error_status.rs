@@ -0,0 +1,105 @@
+use axum::http::StatusCode;
+
+/// Implement this on a custom error type (a `thiserror` enum of domain
+/// errors, for example) to give each variant its own HTTP status and
+/// public message, instead of it collapsing to `500` via the blanket
+/// `From<AnyhowError>` impl on `RouteError`.
+///
+/// Build a `RouteError` from an implementor with
+/// `RouteError::from_status_err`, which keeps the status code and
+/// message while still capturing the underlying error for internal
+/// exposure.
+pub trait RouteErrorStatus {
+    /// The HTTP status code this error should map to.
+    fn route_status(&self) -> StatusCode;
+
+    /// The message shown to the end user.
+    ///
+    /// Defaults to `None`, which falls back to the status code's
+    /// standard public message.
+    fn public_message(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test_route_error_status {
+    use super::*;
+    use crate::RouteError;
+    use crate::RouteInternalError;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct PlainError;
+
+    impl fmt::Display for PlainError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "plain error")
+        }
+    }
+
+    impl std::error::Error for PlainError {}
+
+    impl RouteErrorStatus for PlainError {
+        fn route_status(&self) -> StatusCode {
+            StatusCode::NOT_FOUND
+        }
+    }
+
+    #[derive(Debug)]
+    struct CustomMessageError;
+
+    impl fmt::Display for CustomMessageError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "custom message error")
+        }
+    }
+
+    impl std::error::Error for CustomMessageError {}
+
+    impl RouteErrorStatus for CustomMessageError {
+        fn route_status(&self) -> StatusCode {
+            StatusCode::BAD_REQUEST
+        }
+
+        fn public_message(&self) -> Option<String> {
+            Some("Passwords must be at least 8 characters".to_string())
+        }
+    }
+
+    #[test]
+    fn it_should_fall_back_to_the_status_codes_public_message_by_default() {
+        let err: RouteError = RouteError::from_status_err(PlainError);
+
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(err.public_error_message(), "The resource was not found");
+    }
+
+    #[test]
+    fn it_should_honour_a_custom_public_message() {
+        let err: RouteError = RouteError::from_status_err(CustomMessageError);
+
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            err.public_error_message(),
+            "Passwords must be at least 8 characters"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_still_capture_the_underlying_error_for_internal_exposure() {
+        use axum::response::IntoResponse;
+        use http_body_util::BodyExt;
+        use serde_json::from_slice;
+
+        use crate::RouteErrorOutput;
+
+        let err: RouteInternalError = RouteError::from_status_err(PlainError);
+        let response = err.into_response();
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<RouteErrorOutput<()>>(&response_bytes).unwrap();
+
+        assert_eq!(body.internal_error.unwrap().name, "plain error");
+    }
+}
@@ -10,6 +10,7 @@ mod test_route_internal_error {
     use super::*;
     use crate::RouteErrorOutput;
     use anyhow::anyhow;
+    use anyhow::Context;
     use axum::response::IntoResponse;
     use http_body_util::BodyExt;
     use serde_json::from_slice;
@@ -33,4 +34,25 @@ mod test_route_internal_error {
             "Too many foxes in the DB"
         );
     }
+
+    #[tokio::test]
+    async fn it_should_output_chain_outermost_to_root_cause() {
+        fn raise_error() -> Result<(), RouteInternalError> {
+            let result: Result<(), anyhow::Error> = Err(anyhow!("root cause"));
+            result.context("middle layer").context("outer layer")?;
+
+            Ok(())
+        }
+
+        let err = raise_error().unwrap_err();
+        let response = err.into_response();
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<RouteErrorOutput<()>>(&response_bytes).unwrap();
+
+        assert_eq!(
+            body.internal_error.unwrap().chain,
+            vec!["outer layer", "middle layer", "root cause"],
+        );
+    }
 }
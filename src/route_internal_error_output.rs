@@ -6,4 +6,13 @@ use ::std::fmt::Debug;
 pub struct RouteInternalErrorOutput {
     pub name: String,
     pub debug: String,
+
+    /// The `Display` of each error in the `anyhow::Error` chain,
+    /// ordered from the outermost error down to the root cause.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub chain: Vec<String>,
+
+    /// The captured backtrace, when `RUST_BACKTRACE` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub backtrace: Option<String>,
 }
@@ -0,0 +1,53 @@
+use super::RouteError;
+
+/// This is for **emitting RFC 7807 `application/problem+json` responses**
+/// instead of the crate's default bespoke error shape.
+pub type RouteProblemError<S = ()> = RouteError<S, false, true>;
+
+#[cfg(test)]
+mod test_route_problem_error {
+    use super::*;
+    use anyhow::anyhow;
+    use axum::http::header::CONTENT_TYPE;
+    use axum::response::IntoResponse;
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use serde_json::from_slice;
+
+    #[tokio::test]
+    async fn it_should_output_problem_json() {
+        fn raise_error() -> Result<(), RouteProblemError> {
+            Err(anyhow!("Too many foxes in the DB"))?;
+
+            Ok(())
+        }
+
+        let err = raise_error().unwrap_err().set_request_id("my-request-id");
+        let response = err.into_response();
+
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<Value>(&response_bytes).unwrap();
+
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["status"], 500);
+        assert_eq!(body["request_id"], "my-request-id");
+    }
+
+    #[tokio::test]
+    async fn it_should_output_a_set_problem_detail() {
+        let err: RouteProblemError =
+            RouteProblemError::default().set_problem_detail("the fox ran out of beds");
+        let response = err.into_response();
+        let response_body = response.into_body();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let body = from_slice::<Value>(&response_bytes).unwrap();
+
+        assert_eq!(body["detail"], "the fox ran out of beds");
+    }
+}
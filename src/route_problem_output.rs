@@ -0,0 +1,34 @@
+use ::serde::Deserialize;
+use ::serde::Serialize;
+use ::std::fmt::Debug;
+
+use crate::RouteInternalErrorOutput;
+
+/// The body shape emitted when `RouteError` is configured for RFC 7807
+/// `application/problem+json` output, in place of the bespoke
+/// `RouteErrorOutput` shape.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RouteProblemOutput<S> {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+
+    pub title: String,
+
+    pub status: u16,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal_error: Option<RouteInternalErrorOutput>,
+
+    /// A correlation id for matching this response to its log entry.
+    /// Always present, even when `internal_error` is not exposed.
+    pub request_id: String,
+
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub extra_data: Option<S>,
+}